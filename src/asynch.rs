@@ -0,0 +1,514 @@
+//! Async mirror of the blocking API, for embassy-style executors.
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+use crate::bitfields::{ClockoutCtl, Control1, Control2, Control3, WdCtl};
+use crate::registers::Registers;
+use crate::types::{
+    AlarmConfig, ClockoutFrequency, PowerManagement, PowerStatus, TimestampIndex,
+    WatchdogClockSource, WatchdogConfig,
+};
+use crate::{AlarmError, AsBcd, Pcf2131, DEFAULT_I2C_ADDRESS};
+
+/// Async equivalent of [`crate::RegisterAccess`]
+#[allow(async_fn_in_trait)]
+pub trait AsyncRegisterAccess {
+    type Error;
+
+    async fn write_register(&mut self, register: u8, value: u8) -> Result<(), Self::Error> {
+        self.write_registers(&[register, value]).await
+    }
+
+    /// Write multiple registers. The first value on `reg_and_values` is the start register.
+    async fn write_registers(&mut self, reg_and_values: &[u8]) -> Result<(), Self::Error>;
+
+    async fn read_register(&mut self, register: u8) -> Result<u8, Self::Error> {
+        let mut buf = [0u8];
+        self.read_registers(register, &mut buf).await?;
+
+        Ok(buf[0])
+    }
+
+    async fn read_registers(&mut self, start: u8, buf: &mut [u8]) -> Result<(), Self::Error>;
+}
+
+pub struct AsyncI2CInterface<I2C> {
+    i2c: I2C,
+    address: u8,
+}
+
+impl<I2C> AsyncRegisterAccess for AsyncI2CInterface<I2C>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    type Error = I2C::Error;
+
+    async fn write_registers(&mut self, reg_and_values: &[u8]) -> Result<(), Self::Error> {
+        self.i2c.write(self.address, reg_and_values).await?;
+
+        Ok(())
+    }
+
+    async fn read_registers(&mut self, start: u8, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.i2c.write_read(self.address, &[start], buf).await?;
+
+        Ok(())
+    }
+}
+
+impl<I2C> Pcf2131<AsyncI2CInterface<I2C>>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+{
+    pub async fn new_i2c_async(i2c: I2C) -> Self {
+        Self::new_i2c_addr_async(i2c, DEFAULT_I2C_ADDRESS).await
+    }
+
+    pub async fn new_i2c_addr_async(mut i2c: I2C, address: u8) -> Self {
+        // do a dummy write to the address and ignore the result.
+        // This is done since the i2c interface of the pcf2131 may
+        // be in some weird state if Vdd was lost during a transaction
+        // but the device was still backed by a battery (see 7.16.3)
+        i2c.write(address, &[]).await.ok();
+        i2c.write(address, &[]).await.ok();
+
+        Self {
+            interface: AsyncI2CInterface { i2c, address },
+        }
+    }
+}
+
+impl<I> Pcf2131<I>
+where
+    I: AsyncRegisterAccess,
+{
+    /// Set frequency of the CLKOUT pin
+    pub async fn set_clockout_async(&mut self, freq: ClockoutFrequency) -> Result<(), I::Error> {
+        let mut clockout_ctl = ClockoutCtl::from_bits(
+            self.interface
+                .read_register(Registers::CLOCKOUT_CTL)
+                .await?,
+        );
+        clockout_ctl.set_frequency(freq);
+        self.interface
+            .write_register(Registers::CLOCKOUT_CTL, clockout_ctl.bits())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Stop the clock
+    pub async fn set_stop_async(&mut self, stop: bool) -> Result<(), I::Error> {
+        let mut control_1 =
+            Control1::from_bits(self.interface.read_register(Registers::CONTROL_1).await?);
+        control_1.set_stop(stop);
+        self.interface
+            .write_register(Registers::CONTROL_1, control_1.bits())
+            .await?;
+        Ok(())
+    }
+
+    /// Clear the clock prescaler
+    pub async fn clear_prescaler_async(&mut self) -> Result<(), I::Error> {
+        self.interface
+            .write_register(Registers::SR_RESET, 0xA4)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Set power management options for backup battery
+    pub async fn set_powermanagement_async(
+        &mut self,
+        mode: PowerManagement,
+    ) -> Result<(), I::Error> {
+        let mut control3 =
+            Control3::from_bits(self.interface.read_register(Registers::CONTROL_3).await?);
+        control3.set_power_management(mode);
+        self.interface
+            .write_register(Registers::CONTROL_3, control3.bits())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Perform a OTP refresh
+    ///
+    /// Unlike the blocking version this yields to the executor between
+    /// polls of `CLOCKOUT_CTL` instead of busy-waiting.
+    pub async fn perform_otp_refresh_async(&mut self) -> Result<(), I::Error> {
+        let mut clockout_ctl = ClockoutCtl::from_bits(
+            self.interface
+                .read_register(Registers::CLOCKOUT_CTL)
+                .await?,
+        );
+        clockout_ctl.set_otp_refresh(false);
+        self.interface
+            .write_register(Registers::CLOCKOUT_CTL, clockout_ctl.bits())
+            .await?;
+        clockout_ctl.set_otp_refresh(true);
+        self.interface
+            .write_register(Registers::CLOCKOUT_CTL, clockout_ctl.bits())
+            .await?;
+
+        let mut clockout_ctl = ClockoutCtl::from_bits(
+            self.interface
+                .read_register(Registers::CLOCKOUT_CTL)
+                .await?,
+        );
+        while !clockout_ctl.otp_refresh() {
+            clockout_ctl = ClockoutCtl::from_bits(
+                self.interface
+                    .read_register(Registers::CLOCKOUT_CTL)
+                    .await?,
+            );
+        }
+
+        Ok(())
+    }
+
+    pub async fn datetime_async(&mut self) -> Result<NaiveDateTime, I::Error> {
+        let mut buffer = [0; 8];
+
+        self.interface
+            .read_registers(Registers::SECONDS_100TH, &mut buffer)
+            .await?;
+
+        Ok(NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(
+                buffer[7].as_bcd() as i32 + 2000,
+                buffer[6].as_bcd() as u32,
+                buffer[4].as_bcd() as u32,
+            )
+            .unwrap(),
+            NaiveTime::from_hms_milli_opt(
+                buffer[3].as_bcd() as u32,
+                buffer[2].as_bcd() as u32,
+                (buffer[1] & 0x7F).as_bcd() as u32,
+                buffer[0].as_bcd() as u32 * 10,
+            )
+            .unwrap(),
+        ))
+    }
+
+    /// Read the on-chip day-of-week counter
+    pub async fn weekday_async(&mut self) -> Result<chrono::Weekday, I::Error> {
+        let weekdays = self.interface.read_register(Registers::WEEKDAYS).await?;
+
+        Ok(match weekdays & 0x7 {
+            0 => chrono::Weekday::Sun,
+            1 => chrono::Weekday::Mon,
+            2 => chrono::Weekday::Tue,
+            3 => chrono::Weekday::Wed,
+            4 => chrono::Weekday::Thu,
+            5 => chrono::Weekday::Fri,
+            _ => chrono::Weekday::Sat,
+        })
+    }
+
+    pub async fn set_datetime_async(&mut self, datetime: &NaiveDateTime) -> Result<(), I::Error> {
+        let buffer = [
+            Registers::SECONDS_100TH,
+            0,
+            (datetime.time().second() as u8).to_bcd(),
+            (datetime.time().minute() as u8).to_bcd(),
+            (datetime.time().hour() as u8).to_bcd(),
+            (datetime.date().day() as u8).to_bcd(),
+            datetime.weekday().num_days_from_sunday() as u8,
+            (datetime.date().month() as u8).to_bcd(),
+            ((datetime.date().year() - 2000) as u8).to_bcd(),
+        ];
+
+        self.set_stop_async(true).await?;
+        self.clear_prescaler_async().await?;
+        self.interface.write_registers(&buffer).await?;
+        self.set_stop_async(false).await?;
+
+        Ok(())
+    }
+
+    /// Configure the second/minute/hour/day/weekday alarm and enable its
+    /// interrupt
+    ///
+    /// Returns [`AlarmError::InvalidField`] if a `Match(value)` field is out
+    /// of range for its register (e.g. a second > 59) without touching the
+    /// hardware.
+    pub async fn set_alarm_async(
+        &mut self,
+        config: AlarmConfig,
+    ) -> Result<(), AlarmError<I::Error>> {
+        config.validate().map_err(AlarmError::InvalidField)?;
+
+        let buffer = [
+            Registers::SECOND_ALARM,
+            config.second.to_regval(true),
+            config.minute.to_regval(true),
+            config.hour.to_regval(true),
+            config.day.to_regval(true),
+            config.weekday.to_regval(false),
+        ];
+        self.interface.write_registers(&buffer).await?;
+
+        let mut control_2 =
+            Control2::from_bits(self.interface.read_register(Registers::CONTROL_2).await?);
+        control_2.set_alarm_interrupt_enabled(true);
+        self.interface
+            .write_register(Registers::CONTROL_2, control_2.bits())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Disable the alarm interrupt. The alarm match registers are left
+    /// untouched and can be re-enabled with [`Self::set_alarm_async`].
+    pub async fn clear_alarm_async(&mut self) -> Result<(), I::Error> {
+        let mut control_2 =
+            Control2::from_bits(self.interface.read_register(Registers::CONTROL_2).await?);
+        control_2.set_alarm_interrupt_enabled(false);
+        self.interface
+            .write_register(Registers::CONTROL_2, control_2.bits())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Check whether the alarm flag (AF) is set
+    pub async fn alarm_fired_async(&mut self) -> Result<bool, I::Error> {
+        let control_2 =
+            Control2::from_bits(self.interface.read_register(Registers::CONTROL_2).await?);
+
+        Ok(control_2.alarm_flag())
+    }
+
+    /// Clear the alarm flag (AF)
+    pub async fn clear_alarm_flag_async(&mut self) -> Result<(), I::Error> {
+        let mut control_2 =
+            Control2::from_bits(self.interface.read_register(Registers::CONTROL_2).await?);
+        control_2.set_alarm_flag(false);
+        self.interface
+            .write_register(Registers::CONTROL_2, control_2.bits())
+            .await?;
+
+        Ok(())
+    }
+
+    /// Enable or disable latching of a tamper/power-loss timestamp source
+    pub async fn configure_timestamp_async(
+        &mut self,
+        index: TimestampIndex,
+        enabled: bool,
+    ) -> Result<(), I::Error> {
+        let mut timestp_ctl = self.interface.read_register(Registers::TIMESTP_CTL).await?;
+        if enabled {
+            timestp_ctl |= 1 << index.enable_bit();
+        } else {
+            timestp_ctl &= !(1 << index.enable_bit());
+        }
+        self.interface
+            .write_register(Registers::TIMESTP_CTL, timestp_ctl)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Clear a timestamp's "event recorded" flag so it can latch again
+    pub async fn clear_timestamp_async(&mut self, index: TimestampIndex) -> Result<(), I::Error> {
+        let mut timestp_ctl = self.interface.read_register(Registers::TIMESTP_CTL).await?;
+        timestp_ctl &= !(1 << index.flag_bit());
+        self.interface
+            .write_register(Registers::TIMESTP_CTL, timestp_ctl)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Read a latched tamper/power-loss timestamp
+    ///
+    /// Returns `None` if the source is disabled or hasn't recorded an event.
+    pub async fn read_timestamp_async(
+        &mut self,
+        index: TimestampIndex,
+    ) -> Result<Option<NaiveDateTime>, I::Error> {
+        let timestp_ctl = self.interface.read_register(Registers::TIMESTP_CTL).await?;
+        if timestp_ctl & (1 << index.enable_bit()) == 0
+            || timestp_ctl & (1 << index.flag_bit()) == 0
+        {
+            return Ok(None);
+        }
+
+        let mut buffer = [0u8; 6];
+        self.interface
+            .read_registers(index.data_register(), &mut buffer)
+            .await?;
+
+        Ok(Some(NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(
+                buffer[5].as_bcd() as i32 + 2000,
+                buffer[4].as_bcd() as u32,
+                buffer[3].as_bcd() as u32,
+            )
+            .unwrap(),
+            NaiveTime::from_hms_opt(
+                buffer[2].as_bcd() as u32,
+                buffer[1].as_bcd() as u32,
+                buffer[0].as_bcd() as u32,
+            )
+            .unwrap(),
+        )))
+    }
+
+    /// Read the battery and oscillator status
+    ///
+    /// Check `oscillator_stopped` after a brownout to find out whether the
+    /// last `datetime_async()` read is still trustworthy.
+    pub async fn power_status_async(&mut self) -> Result<PowerStatus, I::Error> {
+        let control_3 =
+            Control3::from_bits(self.interface.read_register(Registers::CONTROL_3).await?);
+        let seconds = self.interface.read_register(Registers::SECONDS).await?;
+
+        Ok(PowerStatus {
+            battery_low: control_3.battery_low_flag(),
+            battery_switchover_active: control_3.battery_switchover_flag(),
+            oscillator_stopped: seconds & (1 << 7) != 0, // OSF
+        })
+    }
+
+    /// Configure and enable the watchdog timer
+    pub async fn set_watchdog_async(&mut self, config: WatchdogConfig) -> Result<(), I::Error> {
+        let mut wd_ctl = WdCtl::from_bits(self.interface.read_register(Registers::WD_CTL).await?);
+        wd_ctl.set_clock_source(config.clock_source);
+        self.interface
+            .write_register(Registers::WD_CTL, wd_ctl.bits())
+            .await?;
+        self.interface
+            .write_register(Registers::WD_VAL, config.value)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Feed (kick) the watchdog, reloading its countdown from WD_VAL
+    pub async fn feed_watchdog_async(&mut self) -> Result<(), I::Error> {
+        let value = self.interface.read_register(Registers::WD_VAL).await?;
+        self.interface
+            .write_register(Registers::WD_VAL, value)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Read back the watchdog's configured clock source
+    pub async fn watchdog_clock_source_async(&mut self) -> Result<WatchdogClockSource, I::Error> {
+        let wd_ctl = WdCtl::from_bits(self.interface.read_register(Registers::WD_CTL).await?);
+        Ok(WatchdogClockSource::from_regval(wd_ctl.clock_source()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    use super::*;
+    use crate::types::AlarmField;
+
+    fn noop_raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw_waker()
+        }
+        fn noop(_: *const ()) {}
+
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(core::ptr::null(), &VTABLE)
+    }
+
+    /// Minimal executor for tests: every future here resolves on its first
+    /// poll since `MockRegisters`'s async fns never actually yield.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+        let mut cx = Context::from_waker(&waker);
+        let mut future = future;
+        let future = unsafe { Pin::new_unchecked(&mut future) };
+
+        match future.poll(&mut cx) {
+            Poll::Ready(value) => value,
+            Poll::Pending => panic!("mock future did not resolve on its first poll"),
+        }
+    }
+
+    /// In-memory register file standing in for a real async I2C/SPI bus.
+    struct MockRegisters {
+        regs: [u8; 64],
+    }
+
+    impl MockRegisters {
+        fn new() -> Self {
+            Self { regs: [0u8; 64] }
+        }
+    }
+
+    impl AsyncRegisterAccess for MockRegisters {
+        type Error = Infallible;
+
+        async fn write_registers(&mut self, reg_and_values: &[u8]) -> Result<(), Self::Error> {
+            let start = reg_and_values[0] as usize;
+            for (i, value) in reg_and_values[1..].iter().enumerate() {
+                self.regs[start + i] = *value;
+            }
+
+            Ok(())
+        }
+
+        async fn read_registers(&mut self, start: u8, buf: &mut [u8]) -> Result<(), Self::Error> {
+            let start = start as usize;
+            buf.copy_from_slice(&self.regs[start..start + buf.len()]);
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_set_stop_async_roundtrip() {
+        let mut pcf = Pcf2131 {
+            interface: MockRegisters::new(),
+        };
+
+        block_on(pcf.set_stop_async(true)).unwrap();
+        assert_eq!(
+            block_on(pcf.interface.read_register(Registers::CONTROL_1)).unwrap(),
+            1 << 5
+        );
+
+        block_on(pcf.set_stop_async(false)).unwrap();
+        assert_eq!(
+            block_on(pcf.interface.read_register(Registers::CONTROL_1)).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_alarm_async_roundtrip() {
+        let mut pcf = Pcf2131 {
+            interface: MockRegisters::new(),
+        };
+
+        let config = AlarmConfig {
+            second: AlarmField::Match(30),
+            minute: AlarmField::Disabled,
+            hour: AlarmField::Disabled,
+            day: AlarmField::Disabled,
+            weekday: AlarmField::Disabled,
+        };
+        block_on(pcf.set_alarm_async(config)).unwrap();
+
+        let control_2 = block_on(pcf.interface.read_register(Registers::CONTROL_2)).unwrap();
+        assert_ne!(control_2 & (1 << 1), 0); // AIE set by set_alarm_async
+        assert!(!block_on(pcf.alarm_fired_async()).unwrap());
+
+        block_on(pcf.clear_alarm_async()).unwrap();
+        let control_2 = block_on(pcf.interface.read_register(Registers::CONTROL_2)).unwrap();
+        assert_eq!(control_2 & (1 << 1), 0); // AIE cleared by clear_alarm_async
+    }
+}
@@ -0,0 +1,218 @@
+//! Typed wrappers around the PCF2131's bitfield registers
+//!
+//! Every method on [`crate::Pcf2131`] used to hand-roll its own
+//! read-modify-write with magic masks (`control_1 |= 1 << 5`,
+//! `control3 &= !0b1110_0000`, ...). These newtypes centralize the bit
+//! layout of CONTROL_1..CONTROL_3, CLOCKOUT_CTL and WD_CTL behind named
+//! getters/setters so the layout only has to be gotten right once.
+
+use crate::types::{ClockoutFrequency, PowerManagement, WatchdogClockSource};
+
+macro_rules! bit_accessor {
+    ($getter:ident, $setter:ident, $bit:expr) => {
+        pub(crate) fn $getter(self) -> bool {
+            self.0 & (1 << $bit) != 0
+        }
+
+        pub(crate) fn $setter(&mut self, enabled: bool) {
+            if enabled {
+                self.0 |= 1 << $bit;
+            } else {
+                self.0 &= !(1 << $bit);
+            }
+        }
+    };
+}
+
+/// Like `bit_accessor!`, but for hardware-set status bits this driver only
+/// ever reads back (e.g. BLF/BSF) — no setter, since nothing writes them.
+macro_rules! bit_getter {
+    ($getter:ident, $bit:expr) => {
+        pub(crate) fn $getter(self) -> bool {
+            self.0 & (1 << $bit) != 0
+        }
+    };
+}
+
+/// CONTROL_1 register (0x00)
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Control1(u8);
+
+impl Control1 {
+    pub(crate) fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    pub(crate) fn bits(self) -> u8 {
+        self.0
+    }
+
+    bit_accessor!(por_override, set_por_override, 3);
+    bit_accessor!(stop, set_stop, 5);
+}
+
+/// CONTROL_2 register (0x01)
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Control2(u8);
+
+impl Control2 {
+    pub(crate) fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    pub(crate) fn bits(self) -> u8 {
+        self.0
+    }
+
+    bit_accessor!(alarm_interrupt_enabled, set_alarm_interrupt_enabled, 1);
+    bit_accessor!(alarm_flag, set_alarm_flag, 4);
+}
+
+/// CONTROL_3 register (0x02)
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Control3(u8);
+
+impl Control3 {
+    pub(crate) fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    pub(crate) fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub(crate) fn power_management(self) -> u8 {
+        (self.0 & 0b1110_0000) >> 5
+    }
+
+    pub(crate) fn set_power_management(&mut self, mode: PowerManagement) {
+        self.0 &= !0b1110_0000;
+        self.0 |= mode.to_regval() << 5;
+    }
+
+    bit_getter!(battery_switchover_flag, 1);
+    bit_getter!(battery_low_flag, 2);
+}
+
+/// CLOCKOUT_CTL register (0x13)
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct ClockoutCtl(u8);
+
+impl ClockoutCtl {
+    pub(crate) fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    pub(crate) fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub(crate) fn frequency(self) -> u8 {
+        self.0 & 0b111
+    }
+
+    pub(crate) fn set_frequency(&mut self, freq: ClockoutFrequency) {
+        self.0 &= !0b111;
+        self.0 |= freq.to_regval();
+    }
+
+    bit_accessor!(otp_refresh, set_otp_refresh, 5);
+}
+
+/// WD_CTL register (0x35)
+///
+/// Only bits[1:0] (WD_CD, the watchdog clock source) are driven by this
+/// crate; the remaining bits are reserved/control aspects of the watchdog
+/// this driver doesn't touch and are preserved as read.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct WdCtl(u8);
+
+impl WdCtl {
+    pub(crate) fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    pub(crate) fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub(crate) fn clock_source(self) -> u8 {
+        self.0 & 0b11
+    }
+
+    pub(crate) fn set_clock_source(&mut self, source: WatchdogClockSource) {
+        self.0 &= !0b11;
+        self.0 |= source.to_regval();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_control1_stop() {
+        let mut control1 = Control1::from_bits(0);
+        assert!(!control1.stop());
+
+        control1.set_stop(true);
+        assert!(control1.stop());
+        assert_eq!(control1.bits(), 1 << 5);
+
+        control1.set_stop(false);
+        assert!(!control1.stop());
+        assert_eq!(control1.bits(), 0);
+    }
+
+    #[test]
+    fn test_control1_por_override() {
+        let mut control1 = Control1::from_bits(0xff);
+        control1.set_por_override(false);
+        assert_eq!(control1.bits(), 0xff & !(1 << 3));
+    }
+
+    #[test]
+    fn test_control2_alarm_bits() {
+        let mut control2 = Control2::from_bits(0);
+        control2.set_alarm_interrupt_enabled(true);
+        assert!(control2.alarm_interrupt_enabled());
+        assert!(!control2.alarm_flag());
+
+        control2.set_alarm_flag(true);
+        assert_eq!(control2.bits(), (1 << 1) | (1 << 4));
+
+        control2.set_alarm_interrupt_enabled(false);
+        assert_eq!(control2.bits(), 1 << 4);
+    }
+
+    #[test]
+    fn test_control3_power_management_roundtrip() {
+        let mut control3 = Control3::from_bits(0);
+        control3.set_power_management(PowerManagement::DirectModeBatteryLowEnabled);
+        assert_eq!(control3.power_management(), 0b011);
+
+        // BLF/BSF are hardware-set status bits with no setter; exercise the
+        // getters directly against a register value with the bit set.
+        let control3_with_blf = Control3::from_bits(control3.bits() | (1 << 2));
+        assert!(control3_with_blf.battery_low_flag());
+        assert!(!control3_with_blf.battery_switchover_flag());
+        assert_eq!(control3_with_blf.power_management(), 0b011);
+    }
+
+    #[test]
+    fn test_clockout_ctl_frequency_roundtrip() {
+        let mut clockout_ctl = ClockoutCtl::from_bits(1 << 5);
+        clockout_ctl.set_frequency(ClockoutFrequency::Hz1024);
+        assert_eq!(clockout_ctl.frequency(), 0b101);
+        assert!(clockout_ctl.otp_refresh());
+    }
+
+    #[test]
+    fn test_wd_ctl_clock_source_roundtrip() {
+        let mut wd_ctl = WdCtl::from_bits(0b1111_0000);
+        wd_ctl.set_clock_source(WatchdogClockSource::Hz4);
+        assert_eq!(wd_ctl.clock_source(), 0b10);
+        // reserved bits above the clock source are left untouched
+        assert_eq!(wd_ctl.bits(), 0b1111_0010);
+    }
+}
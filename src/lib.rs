@@ -6,12 +6,21 @@ use embedded_hal::i2c::I2c;
 // re-export `DateTImeAccess` so crates don't have to depend on `rtcc`
 pub use rtcc::DateTimeAccess;
 
+#[cfg(feature = "async")]
+mod asynch;
+mod bitfields;
 mod registers;
 mod types;
 
+use crate::bitfields::{ClockoutCtl, Control1, Control2, Control3, WdCtl};
 use crate::registers::Registers;
 
-pub use types::{ClockoutFrequency, PowerManagement};
+#[cfg(feature = "async")]
+pub use asynch::{AsyncI2CInterface, AsyncRegisterAccess};
+pub use types::{
+    AlarmConfig, AlarmError, AlarmField, AlarmFieldError, ClockoutFrequency, PowerManagement,
+    PowerStatus, TimestampIndex, WatchdogClockSource, WatchdogConfig,
+};
 
 /// Default I2C address of the PCF2131
 pub const DEFAULT_I2C_ADDRESS: u8 = 0x53;
@@ -61,6 +70,50 @@ where
     }
 }
 
+/// Bit set in the first transmitted byte of a SPI frame to mark it as a read
+const SPI_READ_FLAG: u8 = 0x80;
+
+/// SPI interface for the PCF2131
+///
+/// The first byte of an SPI transfer is the register subaddress, with bit 7
+/// used as the R/W flag (1 = read, 0 = write). On a write the remaining
+/// bytes are the payload; on a read the controller clocks out dummy bytes
+/// while capturing the payload on MISO. The subaddress auto-increments
+/// across a burst exactly like the I2C interface.
+pub struct SpiInterface<SPI> {
+    spi: SPI,
+}
+
+impl<SPI> SpiInterface<SPI>
+where
+    SPI: embedded_hal::spi::SpiDevice,
+{
+    fn new(spi: SPI) -> Self {
+        Self { spi }
+    }
+}
+
+impl<SPI> RegisterAccess for SpiInterface<SPI>
+where
+    SPI: embedded_hal::spi::SpiDevice,
+{
+    type Error = SPI::Error;
+
+    fn write_registers(&mut self, reg_and_values: &[u8]) -> Result<(), Self::Error> {
+        // reg_and_values already starts with the subaddress with bit 7
+        // clear, so it can be sent as-is.
+        self.spi.write(reg_and_values)
+    }
+
+    fn read_registers(&mut self, start: u8, buf: &mut [u8]) -> Result<(), Self::Error> {
+        use embedded_hal::spi::Operation;
+
+        let header = [start | SPI_READ_FLAG];
+        self.spi
+            .transaction(&mut [Operation::Write(&header), Operation::Read(buf)])
+    }
+}
+
 /// PCF2131 driver
 pub struct Pcf2131<I> {
     interface: I,
@@ -88,6 +141,17 @@ where
     }
 }
 
+impl<SPI> Pcf2131<SpiInterface<SPI>>
+where
+    SPI: embedded_hal::spi::SpiDevice,
+{
+    pub fn new_spi(spi: SPI) -> Self {
+        Self {
+            interface: SpiInterface::new(spi),
+        }
+    }
+}
+
 impl<I> Pcf2131<I>
 where
     I: RegisterAccess,
@@ -98,28 +162,40 @@ where
 
     /// Set frequency of the CLKOUT pin
     pub fn set_clockout(&mut self, freq: ClockoutFrequency) -> Result<(), I::Error> {
-        let mut clkcout_ctl = self.interface.read_register(Registers::CLOCKOUT_CTL)?;
-        clkcout_ctl &= !0b111;
-        clkcout_ctl |= freq.to_regval();
+        let mut clockout_ctl =
+            ClockoutCtl::from_bits(self.interface.read_register(Registers::CLOCKOUT_CTL)?);
+        clockout_ctl.set_frequency(freq);
         self.interface
-            .write_register(Registers::CLOCKOUT_CTL, clkcout_ctl)?;
+            .write_register(Registers::CLOCKOUT_CTL, clockout_ctl.bits())?;
 
         Ok(())
     }
 
+    /// Read the current CLKOUT frequency
+    pub fn clockout_frequency(&mut self) -> Result<ClockoutFrequency, I::Error> {
+        let clockout_ctl =
+            ClockoutCtl::from_bits(self.interface.read_register(Registers::CLOCKOUT_CTL)?);
+
+        Ok(ClockoutFrequency::from_regval(clockout_ctl.frequency()))
+    }
+
     /// Stop the clock
     pub fn set_stop(&mut self, stop: bool) -> Result<(), I::Error> {
-        let mut control_1 = self.interface.read_register(Registers::CONTROL_1)?;
-        if stop {
-            control_1 |= 1 << 5;
-        } else {
-            control_1 &= !(1 << 5);
-        }
+        let mut control_1 =
+            Control1::from_bits(self.interface.read_register(Registers::CONTROL_1)?);
+        control_1.set_stop(stop);
         self.interface
-            .write_register(Registers::CONTROL_1, control_1)?;
+            .write_register(Registers::CONTROL_1, control_1.bits())?;
         Ok(())
     }
 
+    /// Check whether the clock is currently stopped
+    pub fn is_stopped(&mut self) -> Result<bool, I::Error> {
+        let control_1 = Control1::from_bits(self.interface.read_register(Registers::CONTROL_1)?);
+
+        Ok(control_1.stop())
+    }
+
     /// Clear the clock prescaler
     pub fn clear_prescaler(&mut self) -> Result<(), I::Error> {
         self.interface.write_register(Registers::SR_RESET, 0xA4)?;
@@ -129,42 +205,242 @@ where
 
     /// Set power management options for backup battery
     pub fn set_powermanagement(&mut self, mode: PowerManagement) -> Result<(), I::Error> {
-        let mut control3 = self.interface.read_register(Registers::CONTROL_3)?;
-        control3 &= !0b1110_0000;
-        control3 |= mode.to_regval() << 5;
+        let mut control3 = Control3::from_bits(self.interface.read_register(Registers::CONTROL_3)?);
+        control3.set_power_management(mode);
         self.interface
-            .write_register(Registers::CONTROL_3, control3)?;
+            .write_register(Registers::CONTROL_3, control3.bits())?;
 
         Ok(())
     }
 
+    /// Read back the configured power management mode
+    pub fn power_management(&mut self) -> Result<PowerManagement, I::Error> {
+        let control3 = Control3::from_bits(self.interface.read_register(Registers::CONTROL_3)?);
+
+        Ok(PowerManagement::from_regval(control3.power_management()))
+    }
+
     /// Disable the POR override
     pub fn disable_por_override(&mut self) -> Result<(), I::Error> {
-        let mut control1 = self.interface.read_register(Registers::CONTROL_1)?;
-        control1 &= !(1 << 3);
+        let mut control1 = Control1::from_bits(self.interface.read_register(Registers::CONTROL_1)?);
+        control1.set_por_override(false);
         self.interface
-            .write_register(Registers::CONTROL_1, control1)?;
+            .write_register(Registers::CONTROL_1, control1.bits())?;
 
         Ok(())
     }
 
+    /// Check whether the POR override is currently enabled
+    pub fn por_override_enabled(&mut self) -> Result<bool, I::Error> {
+        let control1 = Control1::from_bits(self.interface.read_register(Registers::CONTROL_1)?);
+
+        Ok(control1.por_override())
+    }
+
     /// Perform a OTP refresh
     pub fn perform_otp_refresh(&mut self) -> Result<(), I::Error> {
-        let mut clockout = self.interface.read_register(Registers::CLOCKOUT_CTL)?;
-        clockout &= !(1 << 5);
+        let mut clockout_ctl =
+            ClockoutCtl::from_bits(self.interface.read_register(Registers::CLOCKOUT_CTL)?);
+        clockout_ctl.set_otp_refresh(false);
+        self.interface
+            .write_register(Registers::CLOCKOUT_CTL, clockout_ctl.bits())?;
+        clockout_ctl.set_otp_refresh(true);
+        self.interface
+            .write_register(Registers::CLOCKOUT_CTL, clockout_ctl.bits())?;
+
+        loop {
+            clockout_ctl =
+                ClockoutCtl::from_bits(self.interface.read_register(Registers::CLOCKOUT_CTL)?);
+            if clockout_ctl.otp_refresh() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Configure the second/minute/hour/day/weekday alarm and enable its
+    /// interrupt
+    ///
+    /// Returns [`AlarmError::InvalidField`] if a `Match(value)` field is out
+    /// of range for its register (e.g. a second > 59) without touching the
+    /// hardware.
+    pub fn set_alarm(&mut self, config: AlarmConfig) -> Result<(), AlarmError<I::Error>> {
+        config.validate().map_err(AlarmError::InvalidField)?;
+
+        let buffer = [
+            Registers::SECOND_ALARM,
+            config.second.to_regval(true),
+            config.minute.to_regval(true),
+            config.hour.to_regval(true),
+            config.day.to_regval(true),
+            config.weekday.to_regval(false),
+        ];
+        self.interface.write_registers(&buffer)?;
+
+        let mut control_2 =
+            Control2::from_bits(self.interface.read_register(Registers::CONTROL_2)?);
+        control_2.set_alarm_interrupt_enabled(true);
+        self.interface
+            .write_register(Registers::CONTROL_2, control_2.bits())?;
+
+        Ok(())
+    }
+
+    /// Disable the alarm interrupt. The alarm match registers are left
+    /// untouched and can be re-enabled with [`Self::set_alarm`].
+    pub fn clear_alarm(&mut self) -> Result<(), I::Error> {
+        let mut control_2 =
+            Control2::from_bits(self.interface.read_register(Registers::CONTROL_2)?);
+        control_2.set_alarm_interrupt_enabled(false);
+        self.interface
+            .write_register(Registers::CONTROL_2, control_2.bits())?;
+
+        Ok(())
+    }
+
+    /// Check whether the alarm flag (AF) is set
+    pub fn alarm_fired(&mut self) -> Result<bool, I::Error> {
+        let control_2 = Control2::from_bits(self.interface.read_register(Registers::CONTROL_2)?);
+
+        Ok(control_2.alarm_flag())
+    }
+
+    /// Check whether the alarm interrupt (AIE) is currently enabled
+    pub fn alarm_interrupt_enabled(&mut self) -> Result<bool, I::Error> {
+        let control_2 = Control2::from_bits(self.interface.read_register(Registers::CONTROL_2)?);
+
+        Ok(control_2.alarm_interrupt_enabled())
+    }
+
+    /// Clear the alarm flag (AF)
+    pub fn clear_alarm_flag(&mut self) -> Result<(), I::Error> {
+        let mut control_2 =
+            Control2::from_bits(self.interface.read_register(Registers::CONTROL_2)?);
+        control_2.set_alarm_flag(false);
+        self.interface
+            .write_register(Registers::CONTROL_2, control_2.bits())?;
+
+        Ok(())
+    }
+
+    /// Enable or disable latching of a tamper/power-loss timestamp source
+    pub fn configure_timestamp(
+        &mut self,
+        index: TimestampIndex,
+        enabled: bool,
+    ) -> Result<(), I::Error> {
+        let mut timestp_ctl = self.interface.read_register(Registers::TIMESTP_CTL)?;
+        if enabled {
+            timestp_ctl |= 1 << index.enable_bit();
+        } else {
+            timestp_ctl &= !(1 << index.enable_bit());
+        }
         self.interface
-            .write_register(Registers::CLOCKOUT_CTL, clockout)?;
-        clockout |= 1 << 5;
+            .write_register(Registers::TIMESTP_CTL, timestp_ctl)?;
+
+        Ok(())
+    }
+
+    /// Clear a timestamp's "event recorded" flag so it can latch again
+    pub fn clear_timestamp(&mut self, index: TimestampIndex) -> Result<(), I::Error> {
+        let mut timestp_ctl = self.interface.read_register(Registers::TIMESTP_CTL)?;
+        timestp_ctl &= !(1 << index.flag_bit());
         self.interface
-            .write_register(Registers::CLOCKOUT_CTL, clockout)?;
+            .write_register(Registers::TIMESTP_CTL, timestp_ctl)?;
 
-        clockout = self.interface.read_register(Registers::CLOCKOUT_CTL)?;
-        while (clockout & (1 << 5)) == 0 {
-            clockout = self.interface.read_register(Registers::CLOCKOUT_CTL)?;
+        Ok(())
+    }
+
+    /// Read a latched tamper/power-loss timestamp
+    ///
+    /// Returns `None` if the source is disabled or hasn't recorded an event.
+    pub fn read_timestamp(
+        &mut self,
+        index: TimestampIndex,
+    ) -> Result<Option<chrono::NaiveDateTime>, I::Error> {
+        let timestp_ctl = self.interface.read_register(Registers::TIMESTP_CTL)?;
+        if timestp_ctl & (1 << index.enable_bit()) == 0
+            || timestp_ctl & (1 << index.flag_bit()) == 0
+        {
+            return Ok(None);
         }
 
+        let mut buffer = [0u8; 6];
+        self.interface
+            .read_registers(index.data_register(), &mut buffer)?;
+
+        Ok(Some(chrono::NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(
+                buffer[5].as_bcd() as i32 + 2000,
+                buffer[4].as_bcd() as u32,
+                buffer[3].as_bcd() as u32,
+            )
+            .unwrap(),
+            NaiveTime::from_hms_opt(
+                buffer[2].as_bcd() as u32,
+                buffer[1].as_bcd() as u32,
+                buffer[0].as_bcd() as u32,
+            )
+            .unwrap(),
+        )))
+    }
+
+    /// Read the on-chip day-of-week counter
+    pub fn weekday(&mut self) -> Result<chrono::Weekday, I::Error> {
+        let weekdays = self.interface.read_register(Registers::WEEKDAYS)?;
+
+        Ok(match weekdays & 0x7 {
+            0 => chrono::Weekday::Sun,
+            1 => chrono::Weekday::Mon,
+            2 => chrono::Weekday::Tue,
+            3 => chrono::Weekday::Wed,
+            4 => chrono::Weekday::Thu,
+            5 => chrono::Weekday::Fri,
+            _ => chrono::Weekday::Sat,
+        })
+    }
+
+    /// Read the battery and oscillator status
+    ///
+    /// Check `oscillator_stopped` after a brownout to find out whether the
+    /// last `datetime()` read is still trustworthy.
+    pub fn power_status(&mut self) -> Result<PowerStatus, I::Error> {
+        let control_3 = Control3::from_bits(self.interface.read_register(Registers::CONTROL_3)?);
+        let seconds = self.interface.read_register(Registers::SECONDS)?;
+
+        Ok(PowerStatus {
+            battery_low: control_3.battery_low_flag(),
+            battery_switchover_active: control_3.battery_switchover_flag(),
+            oscillator_stopped: seconds & (1 << 7) != 0, // OSF
+        })
+    }
+
+    /// Configure and enable the watchdog timer
+    pub fn set_watchdog(&mut self, config: WatchdogConfig) -> Result<(), I::Error> {
+        let mut wd_ctl = WdCtl::from_bits(self.interface.read_register(Registers::WD_CTL)?);
+        wd_ctl.set_clock_source(config.clock_source);
+        self.interface
+            .write_register(Registers::WD_CTL, wd_ctl.bits())?;
+        self.interface
+            .write_register(Registers::WD_VAL, config.value)?;
+
+        Ok(())
+    }
+
+    /// Feed (kick) the watchdog, reloading its countdown from WD_VAL
+    pub fn feed_watchdog(&mut self) -> Result<(), I::Error> {
+        let value = self.interface.read_register(Registers::WD_VAL)?;
+        self.interface.write_register(Registers::WD_VAL, value)?;
+
         Ok(())
     }
+
+    /// Read back the watchdog's configured clock source
+    pub fn watchdog_clock_source(&mut self) -> Result<WatchdogClockSource, I::Error> {
+        let wd_ctl = WdCtl::from_bits(self.interface.read_register(Registers::WD_CTL)?);
+        Ok(WatchdogClockSource::from_regval(wd_ctl.clock_source()))
+    }
 }
 
 impl<I> DateTimeAccess for Pcf2131<I>
@@ -207,8 +483,7 @@ where
             (datetime.time().minute() as u8).to_bcd(),
             (datetime.time().hour() as u8).to_bcd(),
             (datetime.date().day() as u8).to_bcd(),
-            // weekday?
-            0,
+            datetime.weekday().num_days_from_sunday() as u8,
             (datetime.date().month() as u8).to_bcd(),
             ((datetime.date().year() - 2000) as u8).to_bcd(),
         ];
@@ -222,7 +497,7 @@ where
     }
 }
 
-trait AsBcd {
+pub(crate) trait AsBcd {
     /// Convert the number to Binary Coded Decimal representation
     fn to_bcd(self) -> Self;
 
@@ -254,4 +529,89 @@ mod tests {
         assert_eq!(0x19u8.as_bcd(), 19);
         assert_eq!(0x99u8.as_bcd(), 99);
     }
+
+    #[derive(Debug)]
+    struct MockSpiError;
+
+    impl embedded_hal::spi::Error for MockSpiError {
+        fn kind(&self) -> embedded_hal::spi::ErrorKind {
+            embedded_hal::spi::ErrorKind::Other
+        }
+    }
+
+    /// Fake [`embedded_hal::spi::SpiDevice`] that records the last `Write`
+    /// operation and replays a fixed buffer for `Read` operations.
+    struct MockSpi {
+        last_write: [u8; 8],
+        last_write_len: usize,
+        read_fill: [u8; 8],
+    }
+
+    impl embedded_hal::spi::ErrorType for MockSpi {
+        type Error = MockSpiError;
+    }
+
+    impl embedded_hal::spi::SpiDevice for MockSpi {
+        fn transaction(
+            &mut self,
+            operations: &mut [embedded_hal::spi::Operation<'_, u8>],
+        ) -> Result<(), Self::Error> {
+            for operation in operations {
+                match operation {
+                    embedded_hal::spi::Operation::Write(data) => {
+                        self.last_write[..data.len()].copy_from_slice(data);
+                        self.last_write_len = data.len();
+                    }
+                    embedded_hal::spi::Operation::Read(buf) => {
+                        buf.copy_from_slice(&self.read_fill[..buf.len()]);
+                    }
+                    _ => unreachable!("test only issues Write/Read operations"),
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_spi_interface_write_registers() {
+        let mut interface = SpiInterface::new(MockSpi {
+            last_write: [0; 8],
+            last_write_len: 0,
+            read_fill: [0; 8],
+        });
+
+        interface
+            .write_registers(&[Registers::CONTROL_1, 0xAB])
+            .unwrap();
+
+        assert_eq!(interface.spi.last_write_len, 2);
+        // bit 7 (the read flag) must stay clear on a write
+        assert_eq!(
+            &interface.spi.last_write[..2],
+            &[Registers::CONTROL_1, 0xAB]
+        );
+    }
+
+    #[test]
+    fn test_spi_interface_read_registers() {
+        let mut interface = SpiInterface::new(MockSpi {
+            last_write: [0; 8],
+            last_write_len: 0,
+            read_fill: [0x11, 0x22, 0x33, 0x44, 0, 0, 0, 0],
+        });
+
+        let mut buf = [0u8; 4];
+        interface
+            .read_registers(Registers::SECOND_ALARM, &mut buf)
+            .unwrap();
+
+        // the read flag (bit 7) must be set on the header byte
+        assert_eq!(
+            interface.spi.last_write[0],
+            Registers::SECOND_ALARM | SPI_READ_FLAG
+        );
+        assert_eq!(interface.spi.last_write_len, 1);
+        assert_eq!(buf, [0x11, 0x22, 0x33, 0x44]);
+    }
 }
@@ -17,6 +17,22 @@ impl Registers {
     pub const MONTHS: u8 = 0x0C;
     pub const YEARS: u8 = 0x0D;
 
+    pub const SECOND_ALARM: u8 = 0x0E;
+    pub const MINUTE_ALARM: u8 = 0x0F;
+    pub const HOUR_ALARM: u8 = 0x10;
+    pub const DAY_ALARM: u8 = 0x11;
+    pub const WEEKDAY_ALARM: u8 = 0x12;
+
     pub const CLOCKOUT_CTL: u8 = 0x13;
+
+    pub const TIMESTP_CTL: u8 = 0x14;
+    pub const TIMESTAMP1: u8 = 0x15;
+    pub const TIMESTAMP2: u8 = 0x1B;
+    pub const TIMESTAMP3: u8 = 0x21;
+    pub const TIMESTAMP4: u8 = 0x27;
+
     pub const AGING_OFFSET: u8 = 0x30;
+
+    pub const WD_CTL: u8 = 0x35;
+    pub const WD_VAL: u8 = 0x36;
 }
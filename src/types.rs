@@ -59,6 +59,19 @@ impl ClockoutFrequency {
             ClockoutFrequency::HighZ => 0b111,
         }
     }
+
+    pub(crate) fn from_regval(regval: u8) -> Self {
+        match regval & 0b111 {
+            0b000 => ClockoutFrequency::Hz32768,
+            0b001 => ClockoutFrequency::Hz16384,
+            0b010 => ClockoutFrequency::Hz8192,
+            0b011 => ClockoutFrequency::Hz4096,
+            0b100 => ClockoutFrequency::Hz2048,
+            0b101 => ClockoutFrequency::Hz1024,
+            0b110 => ClockoutFrequency::Hz1,
+            _ => ClockoutFrequency::HighZ,
+        }
+    }
 }
 
 /// Power management options for selecting between Vdd and Vbat
@@ -99,6 +112,185 @@ impl PowerManagement {
             PowerManagement::Disabled => 0b110,                     // and 0b111
         }
     }
+
+    pub(crate) fn from_regval(regval: u8) -> Self {
+        match regval & 0b111 {
+            0b000 => PowerManagement::StandardModeBatteryLowEnabled,
+            0b001 | 0b010 => PowerManagement::StandardModeBatteryLowDisabled,
+            0b011 => PowerManagement::DirectModeBatteryLowEnabled,
+            0b100 | 0b101 => PowerManagement::DirectModeBatteryLowDisabled,
+            _ => PowerManagement::Disabled,
+        }
+    }
+}
+
+/// A single field of an [`AlarmConfig`]
+///
+/// Each alarm register has a dedicated enable bit (bit 7), so every field
+/// of the alarm can be matched independently of the others.
+#[derive(Debug, Clone, Copy)]
+pub enum AlarmField {
+    /// Don't match this field
+    Disabled,
+    /// Match this field against `value`
+    Match(u8),
+}
+
+impl AlarmField {
+    /// Check that a `Match(value)` field is within `min..=max`.
+    ///
+    /// `Disabled` always validates.
+    pub(crate) fn validate(self, min: u8, max: u8) -> Result<(), AlarmFieldError> {
+        match self {
+            AlarmField::Disabled => Ok(()),
+            AlarmField::Match(value) if (min..=max).contains(&value) => Ok(()),
+            AlarmField::Match(_) => Err(AlarmFieldError),
+        }
+    }
+
+    /// Encode the field into its register value.
+    ///
+    /// `bcd` selects whether `value` is encoded as Binary Coded Decimal;
+    /// the weekday alarm register is not BCD encoded.
+    pub(crate) fn to_regval(self, bcd: bool) -> u8 {
+        match self {
+            AlarmField::Disabled => 1 << 7,
+            AlarmField::Match(value) => {
+                if bcd {
+                    crate::AsBcd::to_bcd(value)
+                } else {
+                    value
+                }
+            }
+        }
+    }
+}
+
+/// Configuration for the second/minute/hour/day/weekday alarm
+#[derive(Debug, Clone, Copy)]
+pub struct AlarmConfig {
+    pub second: AlarmField,
+    pub minute: AlarmField,
+    pub hour: AlarmField,
+    pub day: AlarmField,
+    pub weekday: AlarmField,
+}
+
+/// A field in an [`AlarmConfig`] was set to `Match(value)` with `value` out
+/// of range for that field (e.g. a second > 59)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlarmFieldError;
+
+impl AlarmConfig {
+    /// Check that every `Match(value)` field is within range for its
+    /// register (seconds/minutes 0..=59, hours 0..=23, day 1..=31,
+    /// weekday 0..=6). Day is the only field that doesn't start at 0: there
+    /// is no day 0 on the PCF2131's day-alarm register.
+    pub(crate) fn validate(&self) -> Result<(), AlarmFieldError> {
+        self.second.validate(0, 59)?;
+        self.minute.validate(0, 59)?;
+        self.hour.validate(0, 23)?;
+        self.day.validate(1, 31)?;
+        self.weekday.validate(0, 6)?;
+
+        Ok(())
+    }
+}
+
+/// Error returned by [`crate::Pcf2131::set_alarm`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlarmError<E> {
+    /// A field of the `AlarmConfig` was out of range
+    InvalidField(AlarmFieldError),
+    /// The underlying bus returned an error
+    Interface(E),
+}
+
+impl<E> From<E> for AlarmError<E> {
+    fn from(err: E) -> Self {
+        AlarmError::Interface(err)
+    }
+}
+
+/// Selects one of the four tamper/power-loss timestamp sources
+#[derive(Debug, Clone, Copy)]
+pub enum TimestampIndex {
+    Ts1,
+    Ts2,
+    Ts3,
+    Ts4,
+}
+
+impl TimestampIndex {
+    /// Start register of the 6-byte seconds..years data block
+    pub(crate) fn data_register(self) -> u8 {
+        match self {
+            TimestampIndex::Ts1 => crate::registers::Registers::TIMESTAMP1,
+            TimestampIndex::Ts2 => crate::registers::Registers::TIMESTAMP2,
+            TimestampIndex::Ts3 => crate::registers::Registers::TIMESTAMP3,
+            TimestampIndex::Ts4 => crate::registers::Registers::TIMESTAMP4,
+        }
+    }
+
+    /// Bit position of this timestamp's enable bit in TIMESTP_CTL
+    pub(crate) fn enable_bit(self) -> u8 {
+        self as u8
+    }
+
+    /// Bit position of this timestamp's "event recorded" flag in TIMESTP_CTL
+    pub(crate) fn flag_bit(self) -> u8 {
+        4 + self as u8
+    }
+}
+
+/// Battery and oscillator state, decoded from CONTROL_3 and the OSF bit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerStatus {
+    /// Battery low detection (BLF) triggered
+    pub battery_low: bool,
+    /// The battery switch-over circuit is currently supplying the device (BSF)
+    pub battery_switchover_active: bool,
+    /// The oscillator has stopped at some point (OSF), meaning the last
+    /// `datetime()` read can no longer be trusted
+    pub oscillator_stopped: bool,
+}
+
+/// Clock source for the watchdog timer countdown
+#[derive(Debug, Clone, Copy)]
+pub enum WatchdogClockSource {
+    Hz4096,
+    Hz64,
+    Hz4,
+    HzQuarter,
+}
+
+impl WatchdogClockSource {
+    pub(crate) fn to_regval(self) -> u8 {
+        match self {
+            WatchdogClockSource::Hz4096 => 0b00,
+            WatchdogClockSource::Hz64 => 0b01,
+            WatchdogClockSource::Hz4 => 0b10,
+            WatchdogClockSource::HzQuarter => 0b11,
+        }
+    }
+
+    pub(crate) fn from_regval(regval: u8) -> Self {
+        match regval & 0b11 {
+            0b00 => WatchdogClockSource::Hz4096,
+            0b01 => WatchdogClockSource::Hz64,
+            0b10 => WatchdogClockSource::Hz4,
+            _ => WatchdogClockSource::HzQuarter,
+        }
+    }
+}
+
+/// Configuration for the watchdog timer
+#[derive(Debug, Clone, Copy)]
+pub struct WatchdogConfig {
+    /// Clock feeding the countdown in WD_VAL
+    pub clock_source: WatchdogClockSource,
+    /// Countdown value; the watchdog fires when it reaches 0
+    pub value: u8,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -114,3 +306,103 @@ impl TemperaturePeriod {
         self as u8
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alarm_field_to_regval() {
+        assert_eq!(AlarmField::Disabled.to_regval(true), 1 << 7);
+        assert_eq!(AlarmField::Disabled.to_regval(false), 1 << 7);
+
+        assert_eq!(AlarmField::Match(9).to_regval(true), 0x09);
+        assert_eq!(AlarmField::Match(59).to_regval(true), 0x59);
+        assert_eq!(AlarmField::Match(5).to_regval(false), 5);
+    }
+
+    #[test]
+    fn test_alarm_field_validate() {
+        assert_eq!(AlarmField::Disabled.validate(0, 0), Ok(()));
+        assert_eq!(AlarmField::Match(59).validate(0, 59), Ok(()));
+        assert_eq!(AlarmField::Match(60).validate(0, 59), Err(AlarmFieldError));
+        assert_eq!(AlarmField::Match(0).validate(1, 31), Err(AlarmFieldError));
+        assert_eq!(AlarmField::Match(1).validate(1, 31), Ok(()));
+    }
+
+    #[test]
+    fn test_alarm_config_validate() {
+        let valid = AlarmConfig {
+            second: AlarmField::Match(59),
+            minute: AlarmField::Match(59),
+            hour: AlarmField::Match(23),
+            day: AlarmField::Match(31),
+            weekday: AlarmField::Match(6),
+        };
+        assert_eq!(valid.validate(), Ok(()));
+
+        let invalid_hour = AlarmConfig {
+            hour: AlarmField::Match(24),
+            ..valid
+        };
+        assert_eq!(invalid_hour.validate(), Err(AlarmFieldError));
+
+        // day 0 doesn't exist on the PCF2131's day-alarm register
+        let invalid_day = AlarmConfig {
+            day: AlarmField::Match(0),
+            ..valid
+        };
+        assert_eq!(invalid_day.validate(), Err(AlarmFieldError));
+    }
+
+    #[test]
+    fn test_timestamp_index_data_register() {
+        assert_eq!(
+            TimestampIndex::Ts1.data_register(),
+            crate::registers::Registers::TIMESTAMP1
+        );
+        assert_eq!(
+            TimestampIndex::Ts2.data_register(),
+            crate::registers::Registers::TIMESTAMP2
+        );
+        assert_eq!(
+            TimestampIndex::Ts3.data_register(),
+            crate::registers::Registers::TIMESTAMP3
+        );
+        assert_eq!(
+            TimestampIndex::Ts4.data_register(),
+            crate::registers::Registers::TIMESTAMP4
+        );
+    }
+
+    #[test]
+    fn test_timestamp_index_enable_bit() {
+        assert_eq!(TimestampIndex::Ts1.enable_bit(), 0);
+        assert_eq!(TimestampIndex::Ts2.enable_bit(), 1);
+        assert_eq!(TimestampIndex::Ts3.enable_bit(), 2);
+        assert_eq!(TimestampIndex::Ts4.enable_bit(), 3);
+    }
+
+    #[test]
+    fn test_timestamp_index_flag_bit() {
+        assert_eq!(TimestampIndex::Ts1.flag_bit(), 4);
+        assert_eq!(TimestampIndex::Ts2.flag_bit(), 5);
+        assert_eq!(TimestampIndex::Ts3.flag_bit(), 6);
+        assert_eq!(TimestampIndex::Ts4.flag_bit(), 7);
+    }
+
+    #[test]
+    fn test_watchdog_clock_source_roundtrip() {
+        for source in [
+            WatchdogClockSource::Hz4096,
+            WatchdogClockSource::Hz64,
+            WatchdogClockSource::Hz4,
+            WatchdogClockSource::HzQuarter,
+        ] {
+            assert_eq!(
+                WatchdogClockSource::from_regval(source.to_regval()).to_regval(),
+                source.to_regval()
+            );
+        }
+    }
+}